@@ -0,0 +1,80 @@
+use std::path::PathBuf;
+
+use clap::Parser;
+
+use crate::color_order::ColorOrder;
+use crate::frontier::Frontier;
+use crate::metric::ColorSpace;
+
+/// Parse a `usize` CLI argument, rejecting 0 so callers don't have to guard
+/// against it (e.g. `step % stride` in the recorder).
+fn parse_nonzero_usize(s: &str) -> Result<usize, String> {
+    let value: usize = s.parse().map_err(|e: std::num::ParseIntError| e.to_string())?;
+    if value == 0 {
+        Err("must be at least 1".to_string())
+    } else {
+        Ok(value)
+    }
+}
+
+#[derive(Parser, Debug)]
+#[command(author, version, about)]
+pub struct Cli {
+    /// Order in which colors are inserted into the growing image.
+    #[arg(long, value_enum, default_value_t = ColorOrder::Random)]
+    pub order: ColorOrder,
+
+    /// How an empty boundary pixel is scored against the kd-tree.
+    #[arg(long, value_enum, default_value_t = Frontier::Mean)]
+    pub frontier: Frontier,
+
+    /// Color space used to average neighbors and measure nearest-color distance.
+    #[arg(long, value_enum, default_value_t = ColorSpace::Oklab)]
+    pub color_space: ColorSpace,
+
+    /// Image to guide placement toward, so the full set of colors
+    /// approximates this picture instead of forming undirected smoke.
+    #[arg(long)]
+    pub target_image: Option<PathBuf>,
+
+    /// Blend weight of the target image against the neighbor-derived color,
+    /// from 0.0 (ignore the target) to 1.0 (match it exactly).
+    #[arg(long, default_value_t = 0.5)]
+    pub target_weight: f32,
+
+    /// Capture a frame every N placed pixels and write it to `frame_dir`.
+    /// Omit to skip recording entirely.
+    #[arg(long, value_parser = parse_nonzero_usize)]
+    pub frame_stride: Option<usize>,
+
+    /// Directory frame PNGs are written to, when `frame_stride` is set.
+    #[arg(long, default_value = "frames")]
+    pub frame_dir: PathBuf,
+
+    /// Color index used for still-empty pixels in captured frames; leave
+    /// unset to render them black.
+    #[arg(long)]
+    pub unfilled_color: Option<usize>,
+
+    /// Number of placements over which a freshly-placed pixel fades in from
+    /// white, highlighting the growth front. 0 disables the fade.
+    #[arg(long, default_value_t = 0)]
+    pub fade_frames: usize,
+
+    /// Bits per color channel; the full color set has 2^(3 * bits) colors.
+    /// Must be between 1 and 8: 0 would make `scale` divide by zero, and 8
+    /// is the most a `u8` channel can hold.
+    #[arg(long, default_value_t = 8, value_parser = clap::value_parser!(u32).range(1..=8))]
+    pub bits_per_channel: u32,
+
+    /// Canvas width in pixels. Defaults, together with `height`, to the
+    /// squarest power-of-two canvas that exactly fits every color (square
+    /// only when `3 * bits_per_channel` is even; otherwise twice as wide as
+    /// it is tall).
+    #[arg(long)]
+    pub width: Option<usize>,
+
+    /// Canvas height in pixels; see `width`.
+    #[arg(long)]
+    pub height: Option<usize>,
+}
@@ -0,0 +1,171 @@
+use clap::ValueEnum;
+use image::Rgb;
+use rand::seq::SliceRandom;
+
+use crate::metric::ColorSpace;
+
+/// Order in which the colors are handed to the growth loop.
+///
+/// The order is the single biggest driver of the final texture: `Random`
+/// gives the classic "rainbow smoke" noise, while the others bias the walk
+/// toward smoother gradients.
+#[derive(Copy, Clone, Debug, ValueEnum)]
+pub enum ColorOrder {
+    Random,
+    Luminance,
+    Hue,
+    Hilbert,
+}
+
+/// Generate every color in the `bits`-bit-per-channel cube, in `order`, and
+/// convert each to a coordinate in `space`.
+pub fn gen_colors(order: ColorOrder, space: ColorSpace, bits: u32) -> (Vec<Rgb<u8>>, Vec<[f32; 3]>) {
+    let levels = 1u32 << bits;
+    let mut raw = vec![];
+    for r in 0..levels {
+        for g in 0..levels {
+            for b in 0..levels {
+                raw.push([r, g, b]);
+            }
+        }
+    }
+
+    match order {
+        ColorOrder::Random => raw.shuffle(&mut rand::thread_rng()),
+        ColorOrder::Luminance => raw.sort_by(|a, b| {
+            oklab_of(scale(*a, bits))
+                .l
+                .partial_cmp(&oklab_of(scale(*b, bits)).l)
+                .unwrap()
+        }),
+        ColorOrder::Hue => raw.sort_by(|a, b| {
+            hue(scale(*a, bits))
+                .partial_cmp(&hue(scale(*b, bits)))
+                .unwrap()
+        }),
+        ColorOrder::Hilbert => raw.sort_by_key(|c| hilbert_distance(c, bits)),
+    }
+
+    let rgbs: Vec<Rgb<u8>> = raw.iter().map(|&c| scale(c, bits)).collect();
+    let coords = rgbs.iter().map(|&rgb| space.convert(rgb)).collect();
+
+    (rgbs, coords)
+}
+
+/// Spread a `bits`-bit-per-channel color out across the full 0..=255 range
+/// so it still renders sensibly as an 8-bit-per-channel image.
+fn scale(raw: [u32; 3], bits: u32) -> Rgb<u8> {
+    let max_level = (1u32 << bits) - 1;
+    Rgb(raw.map(|c| (c * 255 / max_level) as u8))
+}
+
+/// Snap an arbitrary 8-bit-per-channel color onto the same `bits`-bit-per-
+/// channel grid `scale` produces, so it can be compared against colors this
+/// module generated.
+pub(crate) fn quantize(rgb: Rgb<u8>, bits: u32) -> Rgb<u8> {
+    let max_level = (1u32 << bits) - 1;
+    let raw = rgb.0.map(|c| (c as u32 * max_level + 127) / 255);
+    scale(raw, bits)
+}
+
+fn oklab_of(rgb: Rgb<u8>) -> oklab::Oklab {
+    oklab::srgb_to_oklab(oklab::RGB::new(rgb[0], rgb[1], rgb[2]))
+}
+
+fn hue(rgb: Rgb<u8>) -> f32 {
+    let lab = oklab_of(rgb);
+    lab.b.atan2(lab.a)
+}
+
+/// Index of `raw` along a 3-D Hilbert curve over the `bits`-bit-per-channel
+/// color cube, computed with Skilling's axes/transpose algorithm.
+fn hilbert_distance(raw: &[u32; 3], bits: u32) -> u64 {
+    let mut x = *raw;
+    axes_to_transpose(&mut x, bits);
+    transpose_to_index(&x, bits)
+}
+
+fn axes_to_transpose(x: &mut [u32; 3], bits: u32) {
+    let n = x.len();
+    let m: u32 = 1 << (bits - 1);
+
+    let mut q = m;
+    while q > 1 {
+        let p = q - 1;
+        for i in 0..n {
+            if x[i] & q != 0 {
+                x[0] ^= p;
+            } else {
+                let t = (x[0] ^ x[i]) & p;
+                x[0] ^= t;
+                x[i] ^= t;
+            }
+        }
+        q >>= 1;
+    }
+
+    for i in 1..n {
+        x[i] ^= x[i - 1];
+    }
+
+    let mut t = 0;
+    let mut q = m;
+    while q > 1 {
+        if x[n - 1] & q != 0 {
+            t ^= q - 1;
+        }
+        q >>= 1;
+    }
+    for v in x.iter_mut() {
+        *v ^= t;
+    }
+}
+
+fn transpose_to_index(x: &[u32; 3], bits: u32) -> u64 {
+    let mut index: u64 = 0;
+    for bit in (0..bits).rev() {
+        for &v in x {
+            index = (index << 1) | ((v >> bit) & 1) as u64;
+        }
+    }
+    index
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hilbert_distance_is_a_bijection_onto_0_to_n_cubed() {
+        let bits = 3;
+        let levels = 1u32 << bits;
+
+        let mut indices: Vec<u64> = vec![];
+        for r in 0..levels {
+            for g in 0..levels {
+                for b in 0..levels {
+                    indices.push(hilbert_distance(&[r, g, b], bits));
+                }
+            }
+        }
+
+        indices.sort_unstable();
+        let expected: Vec<u64> = (0..(levels as u64).pow(3)).collect();
+        assert_eq!(indices, expected);
+    }
+
+    #[test]
+    fn scale_and_quantize_round_trip_onto_the_same_grid() {
+        for bits in 1..=8 {
+            let levels = 1u32 << bits;
+            for r in 0..levels {
+                for g in 0..levels {
+                    for b in 0..levels {
+                        let rgb = scale([r, g, b], bits);
+                        assert_eq!(quantize(rgb, bits), rgb);
+                    }
+                }
+            }
+        }
+    }
+}
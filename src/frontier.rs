@@ -0,0 +1,100 @@
+use std::collections::HashMap;
+
+use clap::ValueEnum;
+use toodee::TooDee;
+
+use crate::image_guide::TargetImage;
+use crate::kdforest::KdForest;
+use crate::{empty_neighbors, neighbors, target_color};
+
+/// How an empty boundary pixel is represented as a point in the kd-forest.
+#[derive(Copy, Clone, Debug, ValueEnum)]
+pub enum Frontier {
+    /// One point per boundary pixel, at the mean color of its filled neighbors.
+    Mean,
+    /// One point per (boundary pixel, filled neighbor) pair, so a query
+    /// can land on a single close neighbor rather than an averaged one.
+    Min,
+}
+
+enum Points {
+    Mean(HashMap<(usize, usize), usize>),
+    Min(HashMap<(usize, usize), Vec<usize>>),
+}
+
+/// Owns the kd-forest of available (empty, adjacent-to-filled) pixels along
+/// with whatever bookkeeping its `Frontier` mode needs to keep it in sync.
+pub struct FrontierKdTree {
+    forest: KdForest,
+    points: Points,
+}
+
+impl FrontierKdTree {
+    pub fn new(mode: Frontier) -> Self {
+        FrontierKdTree {
+            forest: KdForest::new(),
+            points: match mode {
+                Frontier::Mean => Points::Mean(HashMap::new()),
+                Frontier::Min => Points::Min(HashMap::new()),
+            },
+        }
+    }
+
+    pub fn nearest(&self, lab: &[f32; 3]) -> (usize, usize) {
+        self.forest.nearest(lab).unwrap()
+    }
+
+    /// Remove every entry standing in for `(x, y)`, e.g. once it's been filled.
+    pub fn remove(&mut self, x: usize, y: usize) {
+        match &mut self.points {
+            Points::Mean(map) => {
+                if let Some(id) = map.remove(&(x, y)) {
+                    self.forest.remove(id);
+                }
+            }
+            Points::Min(map) => {
+                if let Some(ids) = map.remove(&(x, y)) {
+                    for id in ids {
+                        self.forest.remove(id);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Re-derive the forest entries for every still-empty neighbor of `(x, y)`.
+    ///
+    /// When `target` is set, each entry is blended toward the target image's
+    /// color at that pixel, biasing placement so the finished canvas
+    /// resembles it.
+    pub fn add_empty_neighbors(
+        &mut self,
+        x: usize,
+        y: usize,
+        buf: &TooDee<Option<usize>>,
+        colors: &[[f32; 3]],
+        target: Option<&TargetImage>,
+    ) {
+        for (nx, ny) in empty_neighbors(x, y, buf) {
+            self.remove(nx, ny);
+            let blend = |p: [f32; 3]| match target {
+                Some(target) => target.blend(nx, ny, p),
+                None => p,
+            };
+            match &mut self.points {
+                Points::Mean(map) => {
+                    let point = blend(target_color(nx, ny, buf, colors));
+                    let id = self.forest.add(point, (nx, ny));
+                    map.insert((nx, ny), id);
+                }
+                Points::Min(map) => {
+                    let ids = neighbors(nx, ny, buf)
+                        .filter_map(|(_, _, color_idx)| color_idx.map(|c| blend(colors[c])))
+                        .map(|point| self.forest.add(point, (nx, ny)))
+                        .collect();
+                    map.insert((nx, ny), ids);
+                }
+            }
+        }
+    }
+}
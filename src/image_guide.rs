@@ -0,0 +1,71 @@
+use std::collections::HashMap;
+use std::path::Path;
+
+use image::{imageops::FilterType, ImageBuffer, Rgb};
+use toodee::TooDee;
+
+use crate::color_order::quantize;
+use crate::metric::ColorSpace;
+
+/// A target picture resampled onto the canvas, used to steer placement so the
+/// full set of colors ends up arranged to resemble it.
+pub struct TargetImage {
+    rgbs: ImageBuffer<Rgb<u8>, Vec<u8>>,
+    coords: TooDee<[f32; 3]>,
+    weight: f32,
+}
+
+impl TargetImage {
+    pub fn load(path: &Path, width: usize, height: usize, space: ColorSpace, weight: f32) -> Self {
+        let target = image::open(path).unwrap().to_rgb8();
+        let rgbs = image::imageops::resize(
+            &target,
+            width as u32,
+            height as u32,
+            FilterType::Lanczos3,
+        );
+
+        let mut coords = TooDee::new(height, width);
+        for x in 0..width {
+            for y in 0..height {
+                coords[x][y] = space.convert(*rgbs.get_pixel(x as u32, y as u32));
+            }
+        }
+
+        TargetImage {
+            rgbs,
+            coords,
+            weight,
+        }
+    }
+
+    /// Blend a neighbor-derived coordinate toward this target's pixel at `(x, y)`.
+    pub fn blend(&self, x: usize, y: usize, neighbor_coord: [f32; 3]) -> [f32; 3] {
+        let target = self.coords[x][y];
+        let w = self.weight;
+        [
+            (1.0 - w) * neighbor_coord[0] + w * target[0],
+            (1.0 - w) * neighbor_coord[1] + w * target[1],
+            (1.0 - w) * neighbor_coord[2] + w * target[2],
+        ]
+    }
+
+    /// Reorder `rgbs`/`coords` (kept in step) so colors that occur more often
+    /// in the target image are consumed first.
+    ///
+    /// `bits` must be the same bit-per-channel depth `rgbs` was generated
+    /// with, so the target image's full-range pixels are quantized onto the
+    /// same grid before being counted.
+    pub fn reorder_by_histogram(&self, rgbs: &mut Vec<Rgb<u8>>, coords: &mut Vec<[f32; 3]>, bits: u32) {
+        let mut counts: HashMap<[u8; 3], usize> = HashMap::new();
+        for pixel in self.rgbs.pixels() {
+            *counts.entry(quantize(*pixel, bits).0).or_insert(0) += 1;
+        }
+
+        let mut order: Vec<usize> = (0..rgbs.len()).collect();
+        order.sort_by_key(|&i| std::cmp::Reverse(*counts.get(&rgbs[i].0).unwrap_or(&0)));
+
+        *rgbs = order.iter().map(|&i| rgbs[i]).collect();
+        *coords = order.iter().map(|&i| coords[i]).collect();
+    }
+}
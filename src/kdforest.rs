@@ -0,0 +1,148 @@
+use std::collections::{HashMap, HashSet};
+
+use kiddo::KdTree;
+
+use crate::metric::ColorSpace;
+
+/// Tombstones are compacted away once they exceed this fraction of all
+/// entries still referenced by the forest.
+const REBUILD_TOMBSTONE_FRACTION: f32 = 0.5;
+
+type Entry = ([f32; 3], (usize, usize), usize);
+
+struct Slot {
+    tree: KdTree<f32, 3>,
+    coord_of: HashMap<usize, (usize, usize)>,
+    entries: Vec<Entry>,
+}
+
+impl Slot {
+    fn build(entries: Vec<Entry>) -> Self {
+        let mut tree = KdTree::new();
+        let mut coord_of = HashMap::new();
+        for (point, coord, id) in &entries {
+            tree.add(point, *id);
+            coord_of.insert(*id, *coord);
+        }
+        Slot {
+            tree,
+            coord_of,
+            entries,
+        }
+    }
+}
+
+/// A log-structured forest of immutable kd-trees whose sizes are powers of
+/// two, combined upward like a binary counter as points are added. Removals
+/// are tombstones checked at query time rather than structural deletions,
+/// with a full rebuild once tombstones pile up to compact them away.
+///
+/// Exposes the same "find nearest available coordinate" interface as the
+/// single dynamic `KdTree` it replaces, so construction is cheaper without
+/// changing the main loop's results.
+pub struct KdForest {
+    slots: Vec<Option<Slot>>,
+    tombstones: HashSet<usize>,
+    next_id: usize,
+    entry_count: usize,
+}
+
+impl KdForest {
+    pub fn new() -> Self {
+        KdForest {
+            slots: Vec::new(),
+            tombstones: HashSet::new(),
+            next_id: 0,
+            entry_count: 0,
+        }
+    }
+
+    /// Add a point standing in for `coord`, returning an id that can later be
+    /// passed to `remove` to tombstone this exact entry.
+    pub fn add(&mut self, point: [f32; 3], coord: (usize, usize)) -> usize {
+        let id = self.next_id;
+        self.next_id += 1;
+        self.entry_count += 1;
+
+        let mut carry = Some(vec![(point, coord, id)]);
+        let mut level = 0;
+        while let Some(entries) = carry.take() {
+            if level == self.slots.len() {
+                self.slots.push(None);
+            }
+            match self.slots[level].take() {
+                None => self.slots[level] = Some(Slot::build(entries)),
+                Some(existing) => {
+                    let mut combined = existing.entries;
+                    combined.extend(entries);
+                    carry = Some(combined);
+                    level += 1;
+                }
+            }
+        }
+
+        id
+    }
+
+    pub fn remove(&mut self, id: usize) {
+        self.tombstones.insert(id);
+        if self.tombstones.len() as f32 > self.entry_count as f32 * REBUILD_TOMBSTONE_FRACTION {
+            self.rebuild();
+        }
+    }
+
+    fn rebuild(&mut self) {
+        let mut live: Vec<Entry> = self
+            .slots
+            .drain(..)
+            .flatten()
+            .flat_map(|slot| slot.entries)
+            .filter(|(_, _, id)| !self.tombstones.contains(id))
+            .collect();
+        self.tombstones.clear();
+        self.entry_count = live.len();
+
+        let mut slots = Vec::new();
+        let mut level = 0;
+        loop {
+            let size = 1usize << level;
+            if size > self.entry_count {
+                break;
+            }
+            if self.entry_count & size != 0 {
+                let chunk = live.split_off(live.len() - size);
+                slots.push(Some(Slot::build(chunk)));
+            } else {
+                slots.push(None);
+            }
+            level += 1;
+        }
+        self.slots = slots;
+    }
+
+    /// The coordinate of the live point nearest `query`, across every tree in the forest.
+    pub fn nearest(&self, query: &[f32; 3]) -> Option<(usize, usize)> {
+        let mut best: Option<(f32, (usize, usize))> = None;
+
+        for slot in self.slots.iter().flatten() {
+            let (d, id) = slot.tree.nearest_one(query, &ColorSpace::distance);
+            let candidate = if self.tombstones.contains(&id) {
+                slot.entries
+                    .iter()
+                    .filter(|(_, _, id)| !self.tombstones.contains(id))
+                    .map(|(p, coord, _)| (ColorSpace::distance(p, query), *coord))
+                    .min_by(|a, b| a.0.partial_cmp(&b.0).unwrap())
+            } else {
+                Some((d, slot.coord_of[&id]))
+            };
+
+            if let Some(candidate) = candidate {
+                if best.map_or(true, |(bd, _)| candidate.0 < bd) {
+                    best = Some(candidate);
+                }
+            }
+        }
+
+        best.map(|(_, coord)| coord)
+    }
+}
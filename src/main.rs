@@ -1,38 +1,19 @@
-use std::collections::HashMap;
-
+mod cli;
+mod color_order;
+mod frontier;
+mod image_guide;
+mod kdforest;
+mod metric;
+mod recorder;
+
+use clap::Parser;
 use image::ImageBuffer;
-use kiddo::{float::distance::squared_euclidean, KdTree};
-use rand::seq::SliceRandom;
 use toodee::{TooDee, TooDeeOps};
 
-fn gen_colors() -> (Vec<image::Rgb<u8>>, Vec<[f32; 3]>) {
-    let mut rgbs = vec![];
-    for r in 0..=255 {
-        for g in 0..=255 {
-            for b in 0..=255 {
-                rgbs.push(image::Rgb([r, g, b]));
-            }
-        }
-    }
-
-    rgbs.shuffle(&mut rand::thread_rng());
-
-    let mut oklabs = vec![];
-    for rgb in &rgbs {
-        let oklab = oklab::srgb_to_oklab(oklab::RGB::new(rgb[0], rgb[1], rgb[2]));
-        oklabs.push([oklab.l, oklab.a, oklab.b]);
-    }
-
-    (rgbs, oklabs)
-}
-
-fn coord_to_int(x: usize, y: usize) -> usize {
-    (x << 12) | y
-}
-
-fn int_to_coord(i: usize) -> (usize, usize) {
-    (i >> 12, i & ((1 << 12) - 1))
-}
+use cli::Cli;
+use frontier::FrontierKdTree;
+use image_guide::TargetImage;
+use recorder::Recorder;
 
 const NEIGHBORS: &[(isize, isize)] = &[
     (0, 1),
@@ -45,7 +26,7 @@ const NEIGHBORS: &[(isize, isize)] = &[
     (1, -1),
 ];
 
-fn neighbors<T: Copy>(
+pub(crate) fn neighbors<T: Copy>(
     x: usize,
     y: usize,
     data: &TooDee<T>,
@@ -63,7 +44,7 @@ fn neighbors<T: Copy>(
         .map(|(new_x, new_y)| (new_x, new_y, data[new_x][new_y]))
 }
 
-fn empty_neighbors<T: Copy>(
+pub(crate) fn empty_neighbors<T: Copy>(
     x: usize,
     y: usize,
     data: &TooDee<Option<T>>,
@@ -71,7 +52,12 @@ fn empty_neighbors<T: Copy>(
     neighbors(x, y, data).filter_map(|(x, y, ref t)| if t.is_none() { Some((x, y)) } else { None })
 }
 
-fn target_color(x: usize, y: usize, data: &TooDee<Option<usize>>, colors: &[[f32; 3]]) -> [f32; 3] {
+pub(crate) fn target_color(
+    x: usize,
+    y: usize,
+    data: &TooDee<Option<usize>>,
+    colors: &[[f32; 3]],
+) -> [f32; 3] {
     neighbors(x, y, data)
         .flat_map(|(_, _, color_idx)| color_idx.map(|c| (1.0, colors[c])))
         .reduce(|(count, acc), (_, e)| (count + 1.0, [acc[0] + e[0], acc[1] + e[1], acc[2] + e[2]]))
@@ -80,40 +66,140 @@ fn target_color(x: usize, y: usize, data: &TooDee<Option<usize>>, colors: &[[f32
 }
 
 fn main() {
-    let (rgbs, oklabs) = gen_colors();
-    let mut kdtree: KdTree<_, 3> = KdTree::new();
-    let mut available: HashMap<(usize, usize), [f32; 3]> = HashMap::new();
-    let mut buf: TooDee<Option<usize>> = TooDee::new(1 << 12, 1 << 12);
+    let cli = Cli::parse();
+    let num_colors = 1usize << (3 * cli.bits_per_channel);
+    let (width, height) = canvas_size(cli.width, cli.height, num_colors);
+
+    let (mut rgbs, mut coords) =
+        color_order::gen_colors(cli.order, cli.color_space, cli.bits_per_channel);
+    // `TooDee::new` takes (columns, rows) and `buf` is indexed `buf[x][y]`,
+    // so it has one row per x.
+    let mut buf: TooDee<Option<usize>> = TooDee::new(height, width);
+
+    let target = cli.target_image.as_ref().map(|path| {
+        let target = TargetImage::load(
+            path,
+            buf.num_rows(),
+            buf.num_cols(),
+            cli.color_space,
+            cli.target_weight,
+        );
+        target.reorder_by_histogram(&mut rgbs, &mut coords, cli.bits_per_channel);
+        target
+    });
 
-    for (color_idx, lab) in oklabs.iter().enumerate() {
+    let mut frontier = FrontierKdTree::new(cli.frontier);
+    let recorder = cli.frame_stride.map(|stride| {
+        Recorder::new(stride, cli.frame_dir.clone(), cli.unfilled_color, cli.fade_frames)
+    });
+
+    for (color_idx, lab) in coords.iter().enumerate() {
         let (x, y) = if color_idx == 0 {
             (buf.num_rows() / 2, buf.num_cols() / 2)
         } else {
-            int_to_coord(kdtree.nearest_one(lab, &squared_euclidean).1)
+            frontier.nearest(lab)
         };
-        if let Some(old) = available.remove(&(x, y)) {
-            kdtree.remove(&old, coord_to_int(x, y));
-        }
+        frontier.remove(x, y);
         buf[x][y] = Some(color_idx);
+        frontier.add_empty_neighbors(x, y, &buf, &coords, target.as_ref());
 
-        for (nx, ny) in empty_neighbors(x, y, &buf) {
-            if let Some(old) = available.insert((nx, ny), target_color(nx, ny, &buf, &oklabs)) {
-                kdtree.remove(&old, coord_to_int(nx, ny));
-            }
-            kdtree.add(&available[&(nx, ny)], coord_to_int(nx, ny));
+        if let Some(recorder) = &recorder {
+            recorder.maybe_capture(color_idx, &buf, &rgbs);
         }
     }
 
-    let imgbuf = ImageBuffer::from_fn(1 << 12, 1 << 12, |x, y| {
+    let imgbuf = ImageBuffer::from_fn(width as u32, height as u32, |x, y| {
         rgbs[buf[x as usize][y as usize].unwrap()]
     });
 
     imgbuf.save("out.png").unwrap();
 
     let mut all_colors: Vec<usize> = buf.into_iter().flatten().collect();
-    assert_eq!(all_colors.len(), 1 << 24);
+    assert_eq!(all_colors.len(), num_colors);
     all_colors.sort();
     all_colors.iter().enumerate().for_each(|(i, x)| {
         assert_eq!(i, *x);
     });
 }
+
+/// Resolve the canvas dimensions from the CLI's optional `width`/`height`,
+/// defaulting to the squarest canvas that fits `num_colors`, and check they
+/// exactly fit it.
+fn canvas_size(width: Option<usize>, height: Option<usize>, num_colors: usize) -> (usize, usize) {
+    if let Some(width) = width {
+        assert!(width > 0, "--width must be at least 1");
+        assert_eq!(
+            num_colors % width,
+            0,
+            "--width {width} does not evenly divide {num_colors} colors"
+        );
+    }
+    if let Some(height) = height {
+        assert!(height > 0, "--height must be at least 1");
+        assert_eq!(
+            num_colors % height,
+            0,
+            "--height {height} does not evenly divide {num_colors} colors"
+        );
+    }
+
+    let (width, height) = match (width, height) {
+        (Some(width), Some(height)) => (width, height),
+        (Some(width), None) => (width, num_colors / width),
+        (None, Some(height)) => (num_colors / height, height),
+        (None, None) => {
+            // `num_colors` is a power of two but only a perfect square when its
+            // exponent is even, so split the exponent as evenly as possible
+            // between width and height instead of assuming a square root.
+            let exp = num_colors.trailing_zeros();
+            let width_bits = exp.div_ceil(2);
+            let height_bits = exp - width_bits;
+            (1usize << width_bits, 1usize << height_bits)
+        }
+    };
+
+    assert_eq!(
+        width * height,
+        num_colors,
+        "width * height must equal the number of colors (2^(3 * bits_per_channel))"
+    );
+
+    (width, height)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::canvas_size;
+
+    #[test]
+    fn canvas_size_splits_odd_exponents_as_evenly_as_possible() {
+        // bits_per_channel = 5 -> num_colors = 2^15, an odd exponent.
+        assert_eq!(canvas_size(None, None, 1 << 15), (1 << 8, 1 << 7));
+        // bits_per_channel = 6 -> num_colors = 2^18, a perfect square.
+        assert_eq!(canvas_size(None, None, 1 << 18), (1 << 9, 1 << 9));
+    }
+
+    #[test]
+    fn canvas_size_fills_in_the_missing_dimension() {
+        assert_eq!(canvas_size(Some(32), None, 1024), (32, 32));
+        assert_eq!(canvas_size(None, Some(16), 1024), (64, 16));
+    }
+
+    #[test]
+    #[should_panic(expected = "at least 1")]
+    fn canvas_size_rejects_zero_width() {
+        canvas_size(Some(0), None, 1024);
+    }
+
+    #[test]
+    #[should_panic(expected = "at least 1")]
+    fn canvas_size_rejects_zero_height() {
+        canvas_size(None, Some(0), 1024);
+    }
+
+    #[test]
+    #[should_panic(expected = "evenly divide")]
+    fn canvas_size_rejects_non_dividing_width() {
+        canvas_size(Some(7), None, 1024);
+    }
+}
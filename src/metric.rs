@@ -0,0 +1,73 @@
+use clap::ValueEnum;
+use image::Rgb;
+use kiddo::float::distance::squared_euclidean;
+
+/// Color space (and implicitly, distance metric) used to compare colors
+/// while growing the canvas. Averaging neighbors and measuring nearness in
+/// different spaces changes blending behavior meaningfully.
+#[derive(Copy, Clone, Debug, ValueEnum)]
+pub enum ColorSpace {
+    Srgb,
+    CieLab,
+    Oklab,
+}
+
+impl ColorSpace {
+    pub fn convert(&self, rgb: Rgb<u8>) -> [f32; 3] {
+        match self {
+            ColorSpace::Srgb => [
+                rgb[0] as f32 / 255.0,
+                rgb[1] as f32 / 255.0,
+                rgb[2] as f32 / 255.0,
+            ],
+            ColorSpace::CieLab => srgb_to_cielab(rgb),
+            ColorSpace::Oklab => {
+                let lab = oklab::srgb_to_oklab(oklab::RGB::new(rgb[0], rgb[1], rgb[2]));
+                [lab.l, lab.a, lab.b]
+            }
+        }
+    }
+
+    /// Every `ColorSpace` currently shares the same squared Euclidean
+    /// distance; this is an associated function rather than a method since it
+    /// doesn't branch on which space it's called for.
+    pub fn distance(a: &[f32; 3], b: &[f32; 3]) -> f32 {
+        squared_euclidean(a, b)
+    }
+}
+
+fn srgb_to_cielab(rgb: Rgb<u8>) -> [f32; 3] {
+    fn to_linear(c: u8) -> f32 {
+        let c = c as f32 / 255.0;
+        if c <= 0.04045 {
+            c / 12.92
+        } else {
+            ((c + 0.055) / 1.055).powf(2.4)
+        }
+    }
+
+    let r = to_linear(rgb[0]);
+    let g = to_linear(rgb[1]);
+    let b = to_linear(rgb[2]);
+
+    // sRGB (D65) to CIE XYZ.
+    let x = 0.4124564 * r + 0.3575761 * g + 0.1804375 * b;
+    let y = 0.2126729 * r + 0.7151522 * g + 0.0721750 * b;
+    let z = 0.0193339 * r + 0.1191920 * g + 0.9503041 * b;
+
+    // Normalize by the D65 white point, then apply the CIELAB nonlinearity.
+    const DELTA: f32 = 6.0 / 29.0;
+    fn f(t: f32) -> f32 {
+        if t > DELTA * DELTA * DELTA {
+            t.cbrt()
+        } else {
+            t / (3.0 * DELTA * DELTA) + 4.0 / 29.0
+        }
+    }
+
+    let fx = f(x / 0.95047);
+    let fy = f(y / 1.00000);
+    let fz = f(z / 1.08883);
+
+    [116.0 * fy - 16.0, 500.0 * (fx - fy), 200.0 * (fy - fz)]
+}
@@ -0,0 +1,71 @@
+use std::path::PathBuf;
+
+use image::{ImageBuffer, Rgb};
+use toodee::{TooDee, TooDeeOps};
+
+/// Periodically snapshots the growing canvas to disk as a PNG sequence, so
+/// the flood-fill process can be turned into an animation.
+pub struct Recorder {
+    stride: usize,
+    dir: PathBuf,
+    unfilled_color: Option<usize>,
+    fade_frames: usize,
+}
+
+impl Recorder {
+    pub fn new(
+        stride: usize,
+        dir: PathBuf,
+        unfilled_color: Option<usize>,
+        fade_frames: usize,
+    ) -> Self {
+        std::fs::create_dir_all(&dir).unwrap();
+        Recorder {
+            stride,
+            dir,
+            unfilled_color,
+            fade_frames,
+        }
+    }
+
+    /// Capture a frame for placement step `step`, if `step` falls on the configured stride.
+    pub fn maybe_capture(&self, step: usize, buf: &TooDee<Option<usize>>, rgbs: &[Rgb<u8>]) {
+        if step % self.stride != 0 {
+            return;
+        }
+
+        let width = buf.num_rows() as u32;
+        let height = buf.num_cols() as u32;
+        let imgbuf = ImageBuffer::from_fn(width, height, |x, y| {
+            match buf[x as usize][y as usize] {
+                Some(color_idx) => self.faded(step, color_idx, rgbs),
+                None => self.unfilled_color.map_or(Rgb([0, 0, 0]), |c| rgbs[c]),
+            }
+        });
+
+        imgbuf
+            .save(self.dir.join(format!("frame_{:08}.png", step)))
+            .unwrap();
+    }
+
+    fn faded(&self, step: usize, color_idx: usize, rgbs: &[Rgb<u8>]) -> Rgb<u8> {
+        let color = rgbs[color_idx];
+        if self.fade_frames == 0 {
+            return color;
+        }
+        let age = step.saturating_sub(color_idx);
+        if age >= self.fade_frames {
+            return color;
+        }
+        let t = age as f32 / self.fade_frames as f32;
+        Rgb([
+            lerp(255, color[0], t),
+            lerp(255, color[1], t),
+            lerp(255, color[2], t),
+        ])
+    }
+}
+
+fn lerp(from: u8, to: u8, t: f32) -> u8 {
+    (from as f32 * (1.0 - t) + to as f32 * t).round() as u8
+}